@@ -1,14 +1,35 @@
 //! Core distance computation functions without any binding dependencies.
 //! These functions are used by both Python (PyO3) and MATLAB (C FFI) bindings.
+//!
+//! `compute_distance` polls `utils::is_interrupted` once per row of `x1`
+//! (once per series pair for a self-join) so a Ctrl+C during a large
+//! distance-matrix computation unwinds cooperatively (see
+//! `DistanceError::Interrupted`) instead of aborting the process. The
+//! per-cell wavefronts in `diagonal`/`matrix` do not poll the flag, so a
+//! single pairwise alignment (one huge DTW/TWE call, say) runs to
+//! completion before the interrupt is seen. DP kernels implemented inline in this
+//! module instead of going through `diagonal::diagonal_distance` (e.g.
+//! [`twe_soft_with_grad`]) poll per outer row themselves, same as
+//! `compute_distance`.
+//!
+//! The interrupt flag itself is process-wide (set once by the Ctrl+C
+//! handler), but top-level entry points (`compute_distance`, `euclidean`,
+//! `knn`) call `utils::begin_call` instead of clearing it directly, so a
+//! call that starts while another is still running — possible since the
+//! `_async` bindings let genuinely concurrent calls overlap — never wipes
+//! out an interrupt meant for that other call.
 
 use crate::{
     diagonal,
     matrix::WavefrontMatrix,
     utils::{
-        cross_correlation, derivate, dtw_weights, l2_norm, max, min, msm_cost_function, zscore,
+        begin_call, cross_correlation, cross_correlation_direct, derivate, dtw_weights,
+        is_interrupted, l2_norm, max, min, msm_cost_function, zscore,
     },
 };
 use rayon::prelude::*;
+use rustfft::{Fft, FftPlanner, num_complex::Complex};
+use std::sync::Arc;
 use tsdistances_gpu::utils::get_device;
 
 /// Error type for distance computation
@@ -16,6 +37,10 @@ use tsdistances_gpu::utils::get_device;
 pub enum DistanceError {
     InvalidParameter(String),
     ComputationError(String),
+    /// The computation was cancelled cooperatively, e.g. via Ctrl+C.
+    /// Bindings translate this into the host language's native
+    /// interrupt (a `KeyboardInterrupt` in Python).
+    Interrupted,
 }
 
 impl std::fmt::Display for DistanceError {
@@ -23,6 +48,7 @@ impl std::fmt::Display for DistanceError {
         match self {
             DistanceError::InvalidParameter(msg) => write!(f, "Invalid parameter: {msg}"),
             DistanceError::ComputationError(msg) => write!(f, "Computation error: {msg}"),
+            DistanceError::Interrupted => write!(f, "computation was interrupted"),
         }
     }
 }
@@ -31,6 +57,16 @@ impl std::error::Error for DistanceError {}
 
 pub type Result<T> = std::result::Result<T, DistanceError>;
 
+/// Finishes a distance-matrix computation, surfacing `DistanceError::Interrupted`
+/// if a Ctrl+C was observed while `compute_distance` was running.
+fn finish(distance_matrix: Vec<Vec<f64>>) -> Result<Vec<Vec<f64>>> {
+    if is_interrupted() {
+        Err(DistanceError::Interrupted)
+    } else {
+        Ok(distance_matrix)
+    }
+}
+
 fn compute_distance_gpu(
     distance: impl (Fn(&Vec<Vec<f32>>, &Vec<Vec<f32>>) -> Vec<Vec<f32>>) + Sync + Send,
     x1: Vec<Vec<f64>>,
@@ -55,91 +91,254 @@ fn compute_distance_gpu(
 }
 
 /// Computes the pairwise distance between two sets of timeseries.
+///
+/// When `x2` is `None` this is a self-join: the result is symmetric with a
+/// zero diagonal, so only the unique `(i, j)` pairs with `i < j` are
+/// computed and mirrored, roughly halving the work of the general case.
 pub fn compute_distance(
     distance: impl (Fn(&[f64], &[f64]) -> f64) + Sync + Send,
     x1: Vec<Vec<f64>>,
     x2: Option<Vec<Vec<f64>>>,
     par: bool,
 ) -> Vec<Vec<f64>> {
-    let x1 = x1.into_iter().enumerate().collect::<Vec<_>>();
-    let distance_matrix = if par {
-        x1.par_iter()
-            .map(|(i, a)| {
-                if let Some(x2) = &x2 {
-                    x2.iter()
-                        .map(|b| {
-                            let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
-                            distance(a, b)
-                        })
-                        .collect::<Vec<_>>()
-                } else {
-                    x1.iter()
-                        .take(*i)
-                        .map(|(_, b)| {
-                            let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
-                            distance(a, b)
-                        })
-                        .collect::<Vec<_>>()
-                }
-            })
-            .collect::<Vec<_>>()
-    } else {
-        x1.iter()
-            .map(|(i, a)| {
-                if let Some(x2) = &x2 {
-                    x2.iter()
-                        .map(|b| {
-                            let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
-                            distance(a, b)
-                        })
-                        .collect::<Vec<_>>()
-                } else {
-                    x1.iter()
-                        .take(*i)
-                        .map(|(_, b)| {
-                            let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
-                            distance(a, b)
-                        })
-                        .collect::<Vec<_>>()
-                }
+    // Starts a fresh computation, unless another call is already in flight
+    // (possible via the `_async` bindings), in which case an interrupt may
+    // still be pending for that other call and must not be cleared here.
+    let _call_guard = begin_call();
+    match x2 {
+        Some(x2) => compute_distance_cross(distance, &x1, &x2, par),
+        None => compute_distance_symmetric(distance, &x1, par),
+    }
+}
+
+fn compute_distance_cross(
+    distance: impl (Fn(&[f64], &[f64]) -> f64) + Sync + Send,
+    x1: &[Vec<f64>],
+    x2: &[Vec<f64>],
+    par: bool,
+) -> Vec<Vec<f64>> {
+    let compute_row = |a: &Vec<f64>| {
+        if is_interrupted() {
+            return Vec::new();
+        }
+        x2.iter()
+            .map(|b| {
+                let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
+                distance(a, b)
             })
             .collect::<Vec<_>>()
     };
 
-    if x2.is_none() {
-        let mut distance_matrix = distance_matrix;
-        for i in 0..distance_matrix.len() {
-            let row_len = distance_matrix.len();
-            distance_matrix[i].reserve(row_len - i);
-            distance_matrix[i].push(0.0);
-            for j in i + 1..distance_matrix.len() {
-                let d = distance_matrix[j][i];
-                distance_matrix[i].push(d);
-            }
+    if par {
+        x1.par_iter().map(compute_row).collect()
+    } else {
+        x1.iter().map(compute_row).collect()
+    }
+}
+
+fn compute_distance_symmetric(
+    distance: impl (Fn(&[f64], &[f64]) -> f64) + Sync + Send,
+    x1: &[Vec<f64>],
+    par: bool,
+) -> Vec<Vec<f64>> {
+    let n = x1.len();
+    // Every unordered pair of distinct series indices, enumerated once.
+    let pairs: Vec<(usize, usize)> = (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))).collect();
+
+    let compute_pair = |&(i, j): &(usize, usize)| -> (usize, usize, f64) {
+        if is_interrupted() {
+            return (i, j, 0.0);
         }
-        distance_matrix
+        let (a, b) = (&x1[i], &x1[j]);
+        let (a, b) = if a.len() > b.len() { (b, a) } else { (a, b) };
+        (i, j, distance(a, b))
+    };
+
+    let results: Vec<(usize, usize, f64)> = if par {
+        pairs.par_iter().map(compute_pair).collect()
     } else {
-        distance_matrix
+        pairs.iter().map(compute_pair).collect()
+    };
+
+    let mut distance_matrix = vec![vec![0.0; n]; n];
+    for (i, j, d) in results {
+        distance_matrix[i][j] = d;
+        distance_matrix[j][i] = d;
     }
+    distance_matrix
 }
 
 /// Compute Euclidean distance matrix
 pub fn euclidean(x1: Vec<Vec<f64>>, x2: Option<Vec<Vec<f64>>>, par: bool) -> Result<Vec<Vec<f64>>> {
-    let distance_matrix = compute_distance(
-        |a, b| {
-            a.iter()
-                .zip(b.iter())
-                .map(|(x, y)| (x - y).powi(2))
-                .sum::<f64>()
-                .sqrt()
-        },
-        x1,
-        x2,
-        par,
-    );
+    if x1.is_empty() {
+        return Ok(Vec::new());
+    }
+    let d = x1[0].len();
+    if x1.iter().any(|row| row.len() != d) {
+        return Err(DistanceError::InvalidParameter(
+            "All series in x1 must have the same length".to_string(),
+        ));
+    }
+    let is_self_join = x2.is_none();
+    let x2 = x2.unwrap_or_else(|| x1.clone());
+    if x2.iter().any(|row| row.len() != d) {
+        return Err(DistanceError::InvalidParameter(
+            "All series in x2 must have the same length".to_string(),
+        ));
+    }
+
+    let _call_guard = begin_call();
+
+    let norms1: Vec<f64> = x1.iter().map(|row| l2_norm(row).powi(2)).collect();
+    let norms2: Vec<f64> = x2.iter().map(|row| l2_norm(row).powi(2)).collect();
+    let inner = gram_matrix(&x1, &x2, par);
+
+    if is_interrupted() {
+        return Err(DistanceError::Interrupted);
+    }
+
+    let n = x1.len();
+    let m = x2.len();
+    let mut distance_matrix = vec![vec![0.0; m]; n];
+    for i in 0..n {
+        let j_start = if is_self_join { i + 1 } else { 0 };
+        for j in j_start..m {
+            // Floating-point cancellation in the norm decomposition can
+            // produce a tiny negative just below zero for near-identical
+            // rows; clamp before the sqrt.
+            let squared = (norms1[i] + norms2[j] - 2.0 * inner[i][j]).max(0.0);
+            distance_matrix[i][j] = squared.sqrt();
+            if is_self_join {
+                distance_matrix[j][i] = distance_matrix[i][j];
+            }
+        }
+    }
     Ok(distance_matrix)
 }
 
+/// Row/column panel size for `gram_matrix`'s blocking. Each panel holds
+/// `GEMM_BLOCK * d` values, so one row panel, one column panel, and the
+/// output tile they produce fit in a typical 256KB-1MB L2 cache for series
+/// of up to a few hundred samples; longer series give larger panels.
+const GEMM_BLOCK: usize = 64;
+
+/// Copies rows `range` of `src` into one contiguous row-major buffer, so the
+/// inner accumulation loop below walks memory sequentially instead of
+/// chasing each row's separate allocation.
+fn pack_rows(src: &[Vec<f64>], range: std::ops::Range<usize>, d: usize) -> Vec<f64> {
+    let mut packed = Vec::with_capacity(range.len() * d);
+    for row in &src[range] {
+        packed.extend_from_slice(row);
+    }
+    packed
+}
+
+/// Computes the full `X1 . X2^T` inner-product matrix with cache-blocked
+/// row/column panels, parallelizing over row-blocks with rayon when `par`.
+/// This is the O(n·m·d) term in the Gram-matrix decomposition of squared
+/// Euclidean distance; `euclidean` combines it with the precomputed row
+/// norms.
+fn gram_matrix(x1: &[Vec<f64>], x2: &[Vec<f64>], par: bool) -> Vec<Vec<f64>> {
+    let n = x1.len();
+    let m = x2.len();
+    let d = x1[0].len();
+
+    let compute_row_block = |i_start: usize| -> Vec<Vec<f64>> {
+        let i_end = (i_start + GEMM_BLOCK).min(n);
+        let rows = i_end - i_start;
+        let mut block = vec![vec![0.0; m]; rows];
+        if is_interrupted() {
+            return block;
+        }
+
+        let row_panel = pack_rows(x1, i_start..i_end, d);
+        for j_start in (0..m).step_by(GEMM_BLOCK) {
+            let j_end = (j_start + GEMM_BLOCK).min(m);
+            let col_panel = pack_rows(x2, j_start..j_end, d);
+
+            for bi in 0..rows {
+                let a = &row_panel[bi * d..(bi + 1) * d];
+                for bj in 0..(j_end - j_start) {
+                    let b = &col_panel[bj * d..(bj + 1) * d];
+                    let acc: f64 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+                    block[bi][j_start + bj] = acc;
+                }
+            }
+        }
+        block
+    };
+
+    let row_starts: Vec<usize> = (0..n).step_by(GEMM_BLOCK).collect();
+    let blocks: Vec<Vec<Vec<f64>>> = if par {
+        row_starts.par_iter().map(|&s| compute_row_block(s)).collect()
+    } else {
+        row_starts.iter().map(|&s| compute_row_block(s)).collect()
+    };
+
+    blocks.into_iter().flatten().collect()
+}
+
+/// Cosine similarity between `a` and `b`, or `1.0` if either is the zero vector.
+fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let norm_a = l2_norm(a);
+    let norm_b = l2_norm(b);
+    if norm_a < f64::EPSILON || norm_b < f64::EPSILON {
+        return 1.0;
+    }
+    let dot: f64 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    (dot / (norm_a * norm_b)).clamp(-1.0, 1.0)
+}
+
+/// Compute cosine distance matrix: `1 - <a,b> / (||a||*||b||)`
+pub fn cosine(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+    device: &str,
+) -> Result<Vec<Vec<f64>>> {
+    match device {
+        "cpu" => {
+            let distance_matrix =
+                compute_distance(|a, b| 1.0 - cosine_similarity(a, b), x1, x2, par);
+            finish(distance_matrix)
+        }
+        "gpu" => {
+            let distance_matrix = compute_distance_gpu(
+                |a, b| {
+                    let (gpu_device, queue, sba, sda, ma) = get_device();
+                    tsdistances_gpu::cpu::cosine(
+                        gpu_device.clone(),
+                        queue.clone(),
+                        sba.clone(),
+                        sda.clone(),
+                        ma.clone(),
+                        a,
+                        b,
+                    )
+                },
+                x1,
+                x2,
+            );
+            finish(distance_matrix)
+        }
+        _ => Err(DistanceError::InvalidParameter(
+            "Device must be either 'cpu' or 'gpu'".to_string(),
+        )),
+    }
+}
+
+/// Compute angular distance matrix: `arccos(<a,b> / (||a||*||b||))`, in `[0, pi]`.
+///
+/// CPU-only, unlike its sibling [`cosine`]: `tsdistances_gpu` has no
+/// `angular` kernel to dispatch to, and `acos` isn't worth a bespoke GPU
+/// implementation given `cosine_similarity` (the expensive part) is
+/// already shared between the two. Add a `device` parameter here only once
+/// an actual GPU kernel exists to dispatch to.
+pub fn angular(x1: Vec<Vec<f64>>, x2: Option<Vec<Vec<f64>>>, par: bool) -> Result<Vec<Vec<f64>>> {
+    let distance_matrix = compute_distance(|a, b| cosine_similarity(a, b).acos(), x1, x2, par);
+    finish(distance_matrix)
+}
+
 /// Compute Catch22-Euclidean distance matrix
 pub fn catch_euclidean(
     x1: Vec<Vec<f64>>,
@@ -245,6 +444,193 @@ pub fn catch_euclidean(
     euclidean(x1, x2, par)
 }
 
+/// Parameters of the periodic + linear trend model fit by [`lmfit`]:
+/// offset, amplitude, period, phase, trend.
+const LMFIT_PARAMS: usize = 5;
+
+/// Evaluates `offset + amplitude * sin(2*pi*t/period + phase) + trend*t`,
+/// the model [`lmfit`] fits to each series.
+fn lmfit_model(theta: &[f64; LMFIT_PARAMS], t: f64) -> f64 {
+    let [offset, amplitude, period, phase, trend] = *theta;
+    offset + amplitude * (2.0 * std::f64::consts::PI * t / period + phase).sin() + trend * t
+}
+
+/// Analytic Jacobian of the residual `lmfit_model(theta, t) - y` with
+/// respect to each of the 5 parameters, evaluated at time index `t`.
+/// Computing this in closed form avoids the noise a finite-difference
+/// approximation would add to the Levenberg-Marquardt step.
+fn lmfit_jacobian_row(theta: &[f64; LMFIT_PARAMS], t: f64) -> [f64; LMFIT_PARAMS] {
+    let [_, amplitude, period, phase, _] = *theta;
+    let arg = 2.0 * std::f64::consts::PI * t / period + phase;
+    let (sin_arg, cos_arg) = (arg.sin(), arg.cos());
+    [
+        1.0,
+        sin_arg,
+        amplitude * cos_arg * (-2.0 * std::f64::consts::PI * t / period.powi(2)),
+        amplitude * cos_arg,
+        t,
+    ]
+}
+
+/// Solves the dense `n x n` system `a . x = b` by Gaussian elimination with
+/// partial pivoting. `n` is always [`LMFIT_PARAMS`] here, so a
+/// general-purpose linear algebra dependency isn't warranted. Returns
+/// `None` if `a` is (numerically) singular.
+fn solve_linear_system(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| {
+            a[i][col].abs().partial_cmp(&a[j][col].abs()).unwrap()
+        })?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in (col + 1)..n {
+            let factor = a[row][col] / a[col][col];
+            for k in col..n {
+                a[row][k] -= factor * a[col][k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+const LM_MAX_ITERS: usize = 100;
+const LM_INITIAL_LAMBDA: f64 = 1e-3;
+const LM_LAMBDA_DOWN: f64 = 0.3;
+const LM_LAMBDA_UP: f64 = 3.0;
+const LM_TOLERANCE: f64 = 1e-10;
+
+/// Rough initial period estimate for [`lmfit`]'s seeding: the lag in
+/// `2..=n/2` with the highest (biased) autocorrelation, found by a direct
+/// scan. The true period of a real series is almost always far shorter
+/// than the series itself, so starting the solver at `n` reliably lands
+/// it in a bad local minimum; this gives it a
+/// period-scale starting point instead. Falls back to `n` when the series
+/// is too short to have any candidate lag.
+fn estimate_period(y: &[f64]) -> f64 {
+    let n = y.len();
+    let max_lag = n / 2;
+    if max_lag < 2 {
+        return n.max(1) as f64;
+    }
+
+    let mean = y.iter().sum::<f64>() / n as f64;
+    let centered: Vec<f64> = y.iter().map(|v| v - mean).collect();
+
+    (2..=max_lag)
+        .max_by(|&a, &b| {
+            let corr = |lag: usize| -> f64 {
+                (0..n - lag).map(|i| centered[i] * centered[i + lag]).sum()
+            };
+            corr(a).partial_cmp(&corr(b)).unwrap()
+        })
+        .map(|lag| lag as f64)
+        .unwrap_or(n as f64)
+}
+
+/// Fits the periodic + linear trend model to `y` (sampled at unit time
+/// steps) via Levenberg-Marquardt: each iteration solves the damped normal
+/// equations `(JᵀJ + λ·diag(JᵀJ)) Δ = -Jᵀr`, accepts the step and shrinks λ
+/// when it reduces the sum of squared residuals, and otherwise rejects it
+/// and grows λ. Returns the converged parameter vector.
+fn lmfit(y: &[f64]) -> [f64; LMFIT_PARAMS] {
+    let n = y.len();
+    let mean = y.iter().sum::<f64>() / n as f64;
+    let (min, max) = y
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    let mut theta = [mean, (max - min) / 2.0, estimate_period(y), 0.0, 0.0];
+
+    let residuals = |theta: &[f64; LMFIT_PARAMS]| -> Vec<f64> {
+        (0..n).map(|i| lmfit_model(theta, i as f64) - y[i]).collect()
+    };
+    let sum_sq = |r: &[f64]| -> f64 { r.iter().map(|v| v * v).sum() };
+
+    let mut lambda = LM_INITIAL_LAMBDA;
+    let mut r = residuals(&theta);
+    let mut cost = sum_sq(&r);
+
+    for _ in 0..LM_MAX_ITERS {
+        let jac: Vec<[f64; LMFIT_PARAMS]> = (0..n)
+            .map(|i| lmfit_jacobian_row(&theta, i as f64))
+            .collect();
+
+        let mut jtj = vec![vec![0.0; LMFIT_PARAMS]; LMFIT_PARAMS];
+        let mut jtr = vec![0.0; LMFIT_PARAMS];
+        for (row, res) in jac.iter().zip(r.iter()) {
+            for a in 0..LMFIT_PARAMS {
+                jtr[a] += row[a] * res;
+                for b in 0..LMFIT_PARAMS {
+                    jtj[a][b] += row[a] * row[b];
+                }
+            }
+        }
+
+        let mut damped = jtj.clone();
+        for (i, row) in damped.iter_mut().enumerate() {
+            row[i] += lambda * jtj[i][i];
+        }
+        let neg_jtr: Vec<f64> = jtr.iter().map(|v| -v).collect();
+
+        let Some(delta) = solve_linear_system(damped, neg_jtr) else {
+            lambda *= LM_LAMBDA_UP;
+            continue;
+        };
+
+        if delta.iter().map(|v| v.abs()).fold(0.0, f64::max) < LM_TOLERANCE {
+            break;
+        }
+
+        let mut candidate = theta;
+        for (i, val) in candidate.iter_mut().enumerate() {
+            *val += delta[i];
+        }
+        let candidate_r = residuals(&candidate);
+        let candidate_cost = sum_sq(&candidate_r);
+
+        if candidate_cost < cost {
+            theta = candidate;
+            r = candidate_r;
+            cost = candidate_cost;
+            lambda *= LM_LAMBDA_DOWN;
+        } else {
+            lambda *= LM_LAMBDA_UP;
+        }
+    }
+
+    theta
+}
+
+/// Euclidean distance between the Levenberg-Marquardt-fitted parameters of
+/// each series under a periodic + linear trend model
+/// (`offset, amplitude, period, phase, trend`). Like [`catch_euclidean`],
+/// this reduces each series to a fixed-length feature vector before calling
+/// [`euclidean`], but the features here come from a parametric fit rather
+/// than catch22 statistics, giving a distance that's robust to sampling
+/// differences between series of the model shape.
+pub fn lmfit_euclidean(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+) -> Result<Vec<Vec<f64>>> {
+    let x1 = x1.iter().map(|x| lmfit(x).to_vec()).collect();
+    let x2 = x2.map(|x2| x2.iter().map(|x| lmfit(x).to_vec()).collect());
+    euclidean(x1, x2, par)
+}
+
 /// Compute ERP (Edit Distance with Real Penalty) distance matrix
 pub fn erp(
     x1: Vec<Vec<f64>>,
@@ -290,7 +676,7 @@ pub fn erp(
                 x2,
                 par,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         "gpu" => {
             let distance_matrix = compute_distance_gpu(
@@ -310,7 +696,7 @@ pub fn erp(
                 x1,
                 x2,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         _ => Err(DistanceError::InvalidParameter(
             "Device must be either 'cpu' or 'gpu'".to_string(),
@@ -364,7 +750,7 @@ pub fn lcss(
                 x2,
                 par,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         "gpu" => {
             let distance_matrix = compute_distance_gpu(
@@ -384,7 +770,7 @@ pub fn lcss(
                 x1,
                 x2,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         _ => Err(DistanceError::InvalidParameter(
             "Device must be either 'cpu' or 'gpu'".to_string(),
@@ -429,7 +815,7 @@ pub fn dtw(
                 x2,
                 par,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         "gpu" => {
             let distance_matrix = compute_distance_gpu(
@@ -448,7 +834,7 @@ pub fn dtw(
                 x1,
                 x2,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         _ => Err(DistanceError::InvalidParameter(
             "Device must be either 'cpu' or 'gpu'".to_string(),
@@ -511,7 +897,7 @@ pub fn wdtw(
                 x2,
                 par,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         "gpu" => {
             let distance_matrix = compute_distance_gpu(
@@ -533,7 +919,7 @@ pub fn wdtw(
                 x1,
                 x2,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         _ => Err(DistanceError::InvalidParameter(
             "Device must be either 'cpu' or 'gpu'".to_string(),
@@ -599,7 +985,7 @@ pub fn adtw(
                 x2,
                 par,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         "gpu" => {
             let distance_matrix = compute_distance_gpu(
@@ -619,7 +1005,7 @@ pub fn adtw(
                 x1,
                 x2,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         _ => Err(DistanceError::InvalidParameter(
             "Device must be either 'cpu' or 'gpu'".to_string(),
@@ -687,7 +1073,7 @@ pub fn msm(
                 x2,
                 par,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         "gpu" => {
             let distance_matrix = compute_distance_gpu(
@@ -706,7 +1092,7 @@ pub fn msm(
                 x1,
                 x2,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         _ => Err(DistanceError::InvalidParameter(
             "Device must be either 'cpu' or 'gpu'".to_string(),
@@ -783,7 +1169,7 @@ pub fn twe(
                 x2,
                 par,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         "gpu" => {
             let distance_matrix = compute_distance_gpu(
@@ -804,7 +1190,7 @@ pub fn twe(
                 x1,
                 x2,
             );
-            Ok(distance_matrix)
+            finish(distance_matrix)
         }
         _ => Err(DistanceError::InvalidParameter(
             "Device must be either 'cpu' or 'gpu'".to_string(),
@@ -812,13 +1198,271 @@ pub fn twe(
     }
 }
 
-/// Compute SBD (Shape-Based Distance) distance matrix
-pub fn sbd(x1: Vec<Vec<f64>>, x2: Option<Vec<Vec<f64>>>, par: bool) -> Result<Vec<Vec<f64>>> {
+fn softmin(xs: &[f64], gamma: f64) -> (f64, [f64; 3]) {
+    let m = xs.iter().cloned().fold(f64::INFINITY, f64::min);
+    let exps = [
+        (-(xs[0] - m) / gamma).exp(),
+        (-(xs[1] - m) / gamma).exp(),
+        (-(xs[2] - m) / gamma).exp(),
+    ];
+    let sum = exps[0] + exps[1] + exps[2];
+    let value = m - gamma * sum.ln();
+    (value, [exps[0] / sum, exps[1] / sum, exps[2] / sum])
+}
+
+/// Sub-gradient of `|x|` at `x`, with the conventional choice of `0` at the
+/// kink.
+fn abs_sub_gradient(x: f64) -> f64 {
+    if x > 0.0 {
+        1.0
+    } else if x < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+/// Differentiable "soft" TWE and its gradient w.r.t. every element of `a`
+/// and `b`.
+///
+/// Replaces the hard `min(del_a, del_b, match_a_b)` in [`twe`]'s recurrence
+/// with `softmin_gamma(x1,x2,x3) = -gamma*log(sum(exp(-xi/gamma)))`, which
+/// recovers the hard minimum as `gamma -> 0`. The backward pass walks the
+/// same DP table in reverse, propagating adjoints with each transition
+/// weighted by its local routing probability
+/// `exp(-xi/gamma)/sum(exp(-xj/gamma))`, and accumulating the sub-gradient
+/// of each `|·|` cost term. As `gamma -> 0` this gradient approaches the
+/// hard-path subgradient (the gradient through whichever transition the
+/// hard recurrence would have taken).
+///
+/// `gamma` must be `> 0`. Unlike `twe`, this builds an explicit
+/// `(n+1)x(m+1)` DP table instead of going through
+/// `diagonal::diagonal_distance`, since the backward pass needs each cell's
+/// three routing probabilities, which that wavefront traversal doesn't
+/// expose; it does not support a Sakoe-Chiba band yet.
+pub fn twe_soft_with_grad(
+    a: &[f64],
+    b: &[f64],
+    stiffness: f64,
+    penalty: f64,
+    gamma: f64,
+) -> Result<(f64, Vec<f64>, Vec<f64>)> {
+    if gamma <= 0.0 {
+        return Err(DistanceError::InvalidParameter(
+            "gamma must be > 0".to_string(),
+        ));
+    }
+    if stiffness < 0.0 {
+        return Err(DistanceError::InvalidParameter(
+            "Stiffness (nu) must be non-negative".to_string(),
+        ));
+    }
+    if penalty < 0.0 {
+        return Err(DistanceError::InvalidParameter(
+            "Penalty (lambda) must be non-negative".to_string(),
+        ));
+    }
+
+    let n = a.len();
+    let m = b.len();
+    let delete_addition = stiffness + penalty;
+
+    let mut d = vec![vec![0.0; m + 1]; n + 1];
+    // Routing weights (p_del_a, p_del_b, p_match) into cell (i, j).
+    let mut route = vec![vec![[0.0; 3]; m + 1]; n + 1];
+
+    let prev_a = |i: usize| if i >= 2 { a[i - 2] } else { 0.0 };
+    let prev_b = |j: usize| if j >= 2 { b[j - 2] } else { 0.0 };
+
+    for i in 1..=n {
+        d[i][0] = d[i - 1][0] + (prev_a(i) - a[i - 1]).abs() + delete_addition;
+    }
+    for j in 1..=m {
+        d[0][j] = d[0][j - 1] + (prev_b(j) - b[j - 1]).abs() + delete_addition;
+    }
+
+    for i in 1..=n {
+        if is_interrupted() {
+            return Err(DistanceError::Interrupted);
+        }
+        let a_i = a[i - 1];
+        let a_prev = prev_a(i);
+        for j in 1..=m {
+            let b_j = b[j - 1];
+            let b_prev = prev_b(j);
+
+            let del_a = d[i - 1][j] + (a_prev - a_i).abs() + delete_addition;
+            let del_b = d[i][j - 1] + (b_prev - b_j).abs() + delete_addition;
+            let match_cost = d[i - 1][j - 1]
+                + (a_i - b_j).abs()
+                + (a_prev - b_prev).abs()
+                + stiffness * 2.0 * (i as isize - j as isize).unsigned_abs() as f64;
+
+            let (soft, probs) = softmin(&[del_a, del_b, match_cost], gamma);
+            d[i][j] = soft;
+            route[i][j] = probs;
+        }
+    }
+
+    let soft_distance = d[n][m];
+
+    // Backward pass: adjoints flow from (n, m) back to (0, 0), weighted at
+    // each cell by that cell's softmin routing probabilities.
+    let mut adj = vec![vec![0.0; m + 1]; n + 1];
+    adj[n][m] = 1.0;
+    let mut grad_a = vec![0.0; n];
+    let mut grad_b = vec![0.0; m];
+
+    for i in (1..=n).rev() {
+        for j in (1..=m).rev() {
+            let upstream = adj[i][j];
+            if upstream == 0.0 {
+                continue;
+            }
+            let [p_del_a, p_del_b, p_match] = route[i][j];
+            let a_i = a[i - 1];
+            let a_prev = prev_a(i);
+            let b_j = b[j - 1];
+            let b_prev = prev_b(j);
+
+            adj[i - 1][j] += upstream * p_del_a;
+            let g = upstream * p_del_a * abs_sub_gradient(a_prev - a_i);
+            grad_a[i - 1] -= g;
+            if i >= 2 {
+                grad_a[i - 2] += g;
+            }
+
+            adj[i][j - 1] += upstream * p_del_b;
+            let g = upstream * p_del_b * abs_sub_gradient(b_prev - b_j);
+            grad_b[j - 1] -= g;
+            if j >= 2 {
+                grad_b[j - 2] += g;
+            }
+
+            adj[i - 1][j - 1] += upstream * p_match;
+            let g_current = upstream * p_match * abs_sub_gradient(a_i - b_j);
+            grad_a[i - 1] += g_current;
+            grad_b[j - 1] -= g_current;
+            let g_previous = upstream * p_match * abs_sub_gradient(a_prev - b_prev);
+            if i >= 2 {
+                grad_a[i - 2] += g_previous;
+            }
+            if j >= 2 {
+                grad_b[j - 2] -= g_previous;
+            }
+        }
+    }
+    // The boundary row/column only have the (hard) deletion transition.
+    for i in (1..=n).rev() {
+        let upstream = adj[i][0];
+        if upstream == 0.0 {
+            continue;
+        }
+        adj[i - 1][0] += upstream;
+        let g = upstream * abs_sub_gradient(prev_a(i) - a[i - 1]);
+        grad_a[i - 1] -= g;
+        if i >= 2 {
+            grad_a[i - 2] += g;
+        }
+    }
+    for j in (1..=m).rev() {
+        let upstream = adj[0][j];
+        if upstream == 0.0 {
+            continue;
+        }
+        adj[0][j - 1] += upstream;
+        let g = upstream * abs_sub_gradient(prev_b(j) - b[j - 1]);
+        grad_b[j - 1] -= g;
+        if j >= 2 {
+            grad_b[j - 2] += g;
+        }
+    }
+
+    Ok((soft_distance, grad_a, grad_b))
+}
+
+/// Generic elastic distance driven by a user-supplied pointwise local cost,
+/// running through the same `diagonal::diagonal_distance` wavefront as
+/// `dtw`/`erp`/etc. Lets callers define custom cost functions (e.g.
+/// cosine-of-windows, weighted L1) without the crate adding a new named
+/// distance for each one.
+///
+/// `local_cost(a_i, b_j)` is the substitution cost at cell `(i, j)`;
+/// `gap_penalty` is the cost of a deletion/insertion step (must be
+/// non-negative). The recurrence is the same DTW-shaped one used
+/// throughout this module: `local_cost(a_i, b_j) + min(del_a, del_b, match)`.
+pub fn elastic_with_cost(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    gap_penalty: f64,
+    par: bool,
+    local_cost: impl (Fn(f64, f64) -> f64) + Sync + Send,
+) -> Result<Vec<Vec<f64>>> {
+    if gap_penalty < 0.0 {
+        return Err(DistanceError::InvalidParameter(
+            "Gap penalty must be non-negative".to_string(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&sakoe_chiba_band) {
+        return Err(DistanceError::InvalidParameter(
+            "Sakoe-Chiba band must be between 0.0 and 1.0".to_string(),
+        ));
+    }
+
+    let distance_matrix = compute_distance(
+        |a, b| {
+            let cost_func = |a: &[f64], b: &[f64], i: usize, j: usize, x: f64, y: f64, z: f64| {
+                let dist = local_cost(a[i], b[j]);
+                dist + min(min(z + gap_penalty, x + gap_penalty), y)
+            };
+            diagonal::diagonal_distance::<WavefrontMatrix>(
+                a,
+                b,
+                f64::INFINITY,
+                sakoe_chiba_band,
+                cost_func,
+                cost_func,
+                true,
+            )
+        },
+        x1,
+        x2,
+        par,
+    );
+    finish(distance_matrix)
+}
+
+/// Series shorter than this use the direct O(n·m) cross-correlation: below
+/// this length the FFT's planning and buffer setup costs more than the
+/// quadratic loop it replaces.
+const SBD_DIRECT_THRESHOLD: usize = 64;
+
+/// Compute SBD (Shape-Based Distance) distance matrix.
+///
+/// `method` selects the NCCc computation: `"fft"` forces the FFT-based
+/// cross-correlation, `"direct"` forces the brute-force one, and `"auto"`
+/// (or anything else) picks based on series length.
+pub fn sbd(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+    method: &str,
+) -> Result<Vec<Vec<f64>>> {
     let distance_matrix = compute_distance(
         |a, b| {
             let a = zscore(a);
             let b = zscore(b);
-            let cc = cross_correlation(&a, &b);
+            let use_direct = match method {
+                "direct" => true,
+                "fft" => false,
+                _ => a.len().max(b.len()) < SBD_DIRECT_THRESHOLD,
+            };
+            let cc = if use_direct {
+                cross_correlation_direct(&a, &b)
+            } else {
+                cross_correlation(&a, &b)
+            };
             1.0 - cc.iter().max_by(|x, y| x.partial_cmp(y).unwrap()).unwrap()
                 / (l2_norm(&a) * l2_norm(&b))
         },
@@ -826,7 +1470,7 @@ pub fn sbd(x1: Vec<Vec<f64>>, x2: Option<Vec<Vec<f64>>>, par: bool) -> Result<Ve
         x2,
         par,
     );
-    Ok(distance_matrix)
+    finish(distance_matrix)
 }
 
 /// Compute MP (Matrix Profile) distance matrix
@@ -855,7 +1499,53 @@ pub fn mp(
         x2,
         par,
     );
-    Ok(distance_matrix)
+    finish(distance_matrix)
+}
+
+/// Computes the z-normalized Euclidean distance for one (query, window)
+/// cell from its sliding dot product `qt`: the square root of the MASS
+/// identity's squared distance
+/// `2w(1 - (qt - w*mu_q*mu_a) / (w*sigma_q*sigma_a))`. Constant (zero-std)
+/// subsequences are handled separately since the identity divides by
+/// `sigma_q*sigma_a`.
+fn mp_cell_distance(qt: f64, mu_q: f64, sigma_q: f64, mu_a: f64, sigma_a: f64, window: usize) -> f64 {
+    let w = window as f64;
+    let q_is_const = sigma_q.abs() < f64::EPSILON;
+    let a_is_const = sigma_a.abs() < f64::EPSILON;
+    if q_is_const && a_is_const {
+        0.0
+    } else if q_is_const || a_is_const {
+        (2.0 * w).sqrt()
+    } else {
+        let correlation = (qt - w * mu_q * mu_a) / (w * sigma_q * sigma_a);
+        (2.0 * w * (1.0 - correlation)).max(0.0).sqrt()
+    }
+}
+
+/// MASS (Mueen's Algorithm for Similarity Search): computes the sliding dot
+/// products `QT_i` of every length-`window` subsequence of `a` against the
+/// query `q` in O(n log n) via `IFFT(FFT(a) . FFT(reverse(pad(q))))`, which
+/// is the standard convolution-theorem trick for turning a sliding inner
+/// product into an FFT-friendly convolution.
+fn mass_sliding_dot_product(
+    a_fft: &[Complex<f64>],
+    fft: &Arc<dyn Fft<f64>>,
+    ifft: &Arc<dyn Fft<f64>>,
+    fft_len: usize,
+    q: &[f64],
+    n_windows_a: usize,
+) -> Vec<f64> {
+    let window = q.len();
+    let mut q_fft: Vec<Complex<f64>> = q.iter().rev().map(|&v| Complex::new(v, 0.0)).collect();
+    q_fft.resize(fft_len, Complex::new(0.0, 0.0));
+    fft.process(&mut q_fft);
+
+    let mut qt: Vec<Complex<f64>> = a_fft.iter().zip(q_fft.iter()).map(|(x, y)| x * y).collect();
+    ifft.process(&mut qt);
+
+    (0..n_windows_a)
+        .map(|i| qt[window - 1 + i].re / fft_len as f64)
+        .collect()
 }
 
 fn mp_inner(a: &[f64], b: &[f64], window: usize) -> Vec<f64> {
@@ -870,13 +1560,20 @@ fn mp_inner(a: &[f64], b: &[f64], window: usize) -> Vec<f64> {
     let (mean_a, std_a) = mean_std_per_windows(a, window);
     let (mean_b, std_b) = mean_std_per_windows(b, window);
 
-    for (i, sw_a) in a.windows(window).enumerate() {
-        for (j, sw_b) in b.windows(window).enumerate() {
-            let mut dist = 0.0;
-            for (x, y) in sw_a.iter().zip(sw_b.iter()) {
-                dist += (((x - mean_a[i]) / std_a[i]) - ((y - mean_b[j]) / std_b[j])).powi(2);
-            }
-            dist = dist.sqrt();
+    // FFT `a` once and reuse it for every query window of `b`.
+    let fft_len = (n_a + window - 1).next_power_of_two();
+    let mut planner = FftPlanner::new();
+    let fft = planner.plan_fft_forward(fft_len);
+    let ifft = planner.plan_fft_inverse(fft_len);
+
+    let mut a_fft: Vec<Complex<f64>> = a.iter().map(|&v| Complex::new(v, 0.0)).collect();
+    a_fft.resize(fft_len, Complex::new(0.0, 0.0));
+    fft.process(&mut a_fft);
+
+    for (j, q) in b.windows(window).enumerate() {
+        let qts = mass_sliding_dot_product(&a_fft, &fft, &ifft, fft_len, q, p_ab.len());
+        for (i, &qt_i) in qts.iter().enumerate() {
+            let dist = mp_cell_distance(qt_i, mean_b[j], std_b[j], mean_a[i], std_a[i], window);
             p_ab[i] = p_ab[i].min(dist);
             p_ba[j] = p_ba[j].min(dist);
         }
@@ -917,3 +1614,564 @@ fn mean_std_per_windows(a: &[f64], window: usize) -> (Vec<f64>, Vec<f64>) {
 
     (means, stds)
 }
+
+/// One query row's k nearest neighbors: `indices[i]` are into `x2`, ordered
+/// by ascending distance, with `distances[i]` the matching values. Both
+/// inner vectors have length `k` (or fewer, if `x2` has fewer than `k` rows).
+pub struct KnnResult {
+    pub indices: Vec<Vec<usize>>,
+    pub distances: Vec<Vec<f64>>,
+}
+
+/// Max-heap entry ordered by distance so `BinaryHeap` pops the farthest
+/// neighbor first; lets `knn_with_distance` evict it once the heap grows
+/// past `k`. Distances here are never NaN (the kernels that feed this heap
+/// only ever produce finite costs), so `partial_cmp().unwrap()` is safe.
+struct HeapEntry(f64, usize);
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl Eq for HeapEntry {}
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+/// Streams every `x1[i]` against every `x2[j]` through a bounded max-heap of
+/// size `k`, so a query row never holds more than `k` candidates in memory
+/// regardless of how large `x2` is. Parallelizes over query rows with rayon
+/// when `par`, mirroring `compute_distance`'s cross-join path.
+fn knn(
+    distance: impl (Fn(&[f64], &[f64]) -> f64) + Sync + Send,
+    x1: Vec<Vec<f64>>,
+    x2: Vec<Vec<f64>>,
+    k: usize,
+    par: bool,
+) -> Result<KnnResult> {
+    let _call_guard = begin_call();
+    let k = k.min(x2.len().max(1));
+
+    let rows: Vec<(Vec<usize>, Vec<f64>)> = if par {
+        x1.par_iter()
+            .map(|a| {
+                if is_interrupted() {
+                    return (Vec::new(), Vec::new());
+                }
+                let mut heap: std::collections::BinaryHeap<HeapEntry> =
+                    std::collections::BinaryHeap::with_capacity(k + 1);
+                for (j, b) in x2.iter().enumerate() {
+                    heap.push(HeapEntry(distance(a, b), j));
+                    if heap.len() > k {
+                        heap.pop();
+                    }
+                }
+                let mut entries: Vec<HeapEntry> = heap.into_vec();
+                entries.sort_by(|e1, e2| e1.0.partial_cmp(&e2.0).unwrap());
+                entries.into_iter().map(|e| (e.1, e.0)).unzip()
+            })
+            .collect()
+    } else {
+        x1.iter()
+            .map(|a| {
+                if is_interrupted() {
+                    return (Vec::new(), Vec::new());
+                }
+                let mut heap: std::collections::BinaryHeap<HeapEntry> =
+                    std::collections::BinaryHeap::with_capacity(k + 1);
+                for (j, b) in x2.iter().enumerate() {
+                    heap.push(HeapEntry(distance(a, b), j));
+                    if heap.len() > k {
+                        heap.pop();
+                    }
+                }
+                let mut entries: Vec<HeapEntry> = heap.into_vec();
+                entries.sort_by(|e1, e2| e1.0.partial_cmp(&e2.0).unwrap());
+                entries.into_iter().map(|e| (e.1, e.0)).unzip()
+            })
+            .collect()
+    };
+
+    if is_interrupted() {
+        return Err(DistanceError::Interrupted);
+    }
+
+    let (indices, distances) = rows.into_iter().unzip();
+    Ok(KnnResult { indices, distances })
+}
+
+/// k nearest neighbors in `x2` for each row of `x1` under Euclidean
+/// distance, without materializing the full distance matrix.
+pub fn knn_euclidean(x1: Vec<Vec<f64>>, x2: Vec<Vec<f64>>, k: usize, par: bool) -> Result<KnnResult> {
+    knn(
+        |a, b| {
+            a.iter()
+                .zip(b.iter())
+                .map(|(x, y)| (x - y).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        },
+        x1,
+        x2,
+        k,
+        par,
+    )
+}
+
+/// k nearest neighbors in `x2` for each row of `x1` under DTW, reusing the
+/// same `sakoe_chiba_band` semantics as [`dtw`].
+pub fn knn_dtw(
+    x1: Vec<Vec<f64>>,
+    x2: Vec<Vec<f64>>,
+    sakoe_chiba_band: f64,
+    k: usize,
+    par: bool,
+) -> Result<KnnResult> {
+    if !(0.0..=1.0).contains(&sakoe_chiba_band) {
+        return Err(DistanceError::InvalidParameter(
+            "Sakoe-Chiba band must be between 0.0 and 1.0".to_string(),
+        ));
+    }
+
+    knn(
+        |a, b| {
+            let dtw_cost_func =
+                |a: &[f64], b: &[f64], i: usize, j: usize, x: f64, y: f64, z: f64| {
+                    let dist = (a[i] - b[j]).powi(2);
+                    dist + min(min(z, x), y)
+                };
+            diagonal::diagonal_distance::<WavefrontMatrix>(
+                a,
+                b,
+                f64::INFINITY,
+                sakoe_chiba_band,
+                dtw_cost_func,
+                dtw_cost_func,
+                true,
+            )
+        },
+        x1,
+        x2,
+        k,
+        par,
+    )
+}
+
+/// Minimal splitmix64 PRNG, used only for the k-means++-style seeding in
+/// [`kmedoids`] so a given `seed` always reproduces the same medoids
+/// without pulling in an external `rand` dependency for one call site.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+
+/// Cluster assignment from [`kmedoids`]: `labels[i]` is the index into
+/// `medoids` that series `i` was assigned to, and `medoids` holds the row
+/// indices (into the original distance matrix) of the final medoid set.
+pub struct KMedoidsResult {
+    pub labels: Vec<usize>,
+    pub medoids: Vec<usize>,
+}
+
+/// Assigns every point to its nearest medoid under `distance_matrix`.
+fn kmedoids_assign(distance_matrix: &[Vec<f64>], medoids: &[usize]) -> Vec<usize> {
+    let n = distance_matrix.len();
+    (0..n)
+        .map(|i| {
+            medoids
+                .iter()
+                .enumerate()
+                .min_by(|(_, &a), (_, &b)| {
+                    distance_matrix[i][a]
+                        .partial_cmp(&distance_matrix[i][b])
+                        .unwrap()
+                })
+                .map(|(cluster, _)| cluster)
+                .unwrap()
+        })
+        .collect()
+}
+
+fn kmedoids_cost(distance_matrix: &[Vec<f64>], medoids: &[usize], labels: &[usize]) -> f64 {
+    (0..distance_matrix.len())
+        .map(|i| distance_matrix[i][medoids[labels[i]]])
+        .sum()
+}
+
+/// Partitioning Around Medoids (PAM) over a precomputed distance matrix.
+/// Medoids are seeded k-means++-style (first one uniform, each subsequent
+/// one weighted by its squared distance to the nearest medoid chosen so
+/// far), then refined by repeatedly trying every (medoid, non-medoid) swap
+/// and keeping whichever reduces total within-cluster distance the most,
+/// until no swap improves on the current assignment. Because a medoid is
+/// always one of the input points rather than an average, this works with
+/// any distance — including non-Euclidean measures like DTW, MSM, or TWE —
+/// where a centroid isn't well-defined.
+pub fn kmedoids(distance_matrix: &[Vec<f64>], k: usize, seed: u64) -> KMedoidsResult {
+    let n = distance_matrix.len();
+    if n == 0 {
+        return KMedoidsResult {
+            labels: Vec::new(),
+            medoids: Vec::new(),
+        };
+    }
+    let k = k.clamp(1, n);
+    let mut rng = SplitMix64::new(seed);
+
+    let mut medoids = Vec::with_capacity(k);
+    medoids.push((rng.next_u64() as usize) % n);
+    let mut nearest_sq = vec![f64::INFINITY; n];
+    while medoids.len() < k {
+        let last = *medoids.last().unwrap();
+        for (i, slot) in nearest_sq.iter_mut().enumerate() {
+            let d = distance_matrix[i][last];
+            *slot = slot.min(d * d);
+        }
+        let total: f64 = nearest_sq.iter().sum();
+        if total <= 0.0 {
+            match (0..n).find(|i| !medoids.contains(i)) {
+                Some(next) => medoids.push(next),
+                None => break,
+            }
+            continue;
+        }
+        let target = rng.next_f64() * total;
+        let mut cumulative = 0.0;
+        let mut chosen = n - 1;
+        for (i, &weight) in nearest_sq.iter().enumerate() {
+            cumulative += weight;
+            if cumulative >= target {
+                chosen = i;
+                break;
+            }
+        }
+        medoids.push(chosen);
+    }
+
+    let mut labels = kmedoids_assign(distance_matrix, &medoids);
+    let mut cost = kmedoids_cost(distance_matrix, &medoids, &labels);
+
+    loop {
+        let mut best_swap: Option<(usize, usize, f64)> = None;
+        for m_idx in 0..medoids.len() {
+            for candidate in 0..n {
+                if medoids.contains(&candidate) {
+                    continue;
+                }
+                let mut trial = medoids.clone();
+                trial[m_idx] = candidate;
+                let trial_labels = kmedoids_assign(distance_matrix, &trial);
+                let trial_cost = kmedoids_cost(distance_matrix, &trial, &trial_labels);
+                let improves = trial_cost < cost - 1e-12
+                    && best_swap
+                        .map(|(_, _, best_cost)| trial_cost < best_cost)
+                        .unwrap_or(true);
+                if improves {
+                    best_swap = Some((m_idx, candidate, trial_cost));
+                }
+            }
+        }
+
+        match best_swap {
+            Some((m_idx, candidate, new_cost)) => {
+                medoids[m_idx] = candidate;
+                labels = kmedoids_assign(distance_matrix, &medoids);
+                cost = new_cost;
+            }
+            None => break,
+        }
+    }
+
+    KMedoidsResult { labels, medoids }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mp_inner_self_join_matches_are_near_zero() {
+        let a = vec![1.0, 3.0, 2.0, 5.0, 4.0, 6.0, 1.0, 3.0, 2.0, 5.0];
+        let window = 4;
+        let profile = mp_inner(&a, &a, window);
+        for &d in &profile {
+            assert!(
+                d.abs() < 1e-6,
+                "expected near-zero self-match distance, got {d}"
+            );
+        }
+    }
+
+    #[test]
+    fn twe_soft_with_grad_matches_finite_difference() {
+        let a = vec![1.0, 2.0, 1.5, 3.0, 2.5];
+        let b = vec![1.1, 2.2, 1.4, 2.9, 2.6, 3.1];
+        let stiffness = 0.1;
+        let penalty = 0.2;
+        let gamma = 0.5;
+
+        let (_, grad_a, grad_b) = twe_soft_with_grad(&a, &b, stiffness, penalty, gamma).unwrap();
+
+        let eps = 1e-5;
+        for i in 0..a.len() {
+            let mut a_plus = a.clone();
+            a_plus[i] += eps;
+            let (cost_plus, _, _) =
+                twe_soft_with_grad(&a_plus, &b, stiffness, penalty, gamma).unwrap();
+
+            let mut a_minus = a.clone();
+            a_minus[i] -= eps;
+            let (cost_minus, _, _) =
+                twe_soft_with_grad(&a_minus, &b, stiffness, penalty, gamma).unwrap();
+
+            let numerical = (cost_plus - cost_minus) / (2.0 * eps);
+            assert!(
+                (numerical - grad_a[i]).abs() < 1e-2,
+                "grad_a mismatch at {i}: analytic {} vs numerical {numerical}",
+                grad_a[i]
+            );
+        }
+
+        for i in 0..b.len() {
+            let mut b_plus = b.clone();
+            b_plus[i] += eps;
+            let (cost_plus, _, _) =
+                twe_soft_with_grad(&a, &b_plus, stiffness, penalty, gamma).unwrap();
+
+            let mut b_minus = b.clone();
+            b_minus[i] -= eps;
+            let (cost_minus, _, _) =
+                twe_soft_with_grad(&a, &b_minus, stiffness, penalty, gamma).unwrap();
+
+            let numerical = (cost_plus - cost_minus) / (2.0 * eps);
+            assert!(
+                (numerical - grad_b[i]).abs() < 1e-2,
+                "grad_b mismatch at {i}: analytic {} vs numerical {numerical}",
+                grad_b[i]
+            );
+        }
+    }
+
+    #[test]
+    fn euclidean_self_join_has_zero_diagonal_and_is_symmetric() {
+        let x1 = vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![0.0, 1.0, -1.0],
+        ];
+        let d = euclidean(x1, None, false).unwrap();
+        for i in 0..d.len() {
+            assert!(d[i][i].abs() < 1e-9, "expected zero self-distance at {i}");
+            for j in 0..d.len() {
+                assert!(
+                    (d[i][j] - d[j][i]).abs() < 1e-9,
+                    "expected symmetric distance at ({i}, {j})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn euclidean_matches_brute_force() {
+        let x1 = vec![vec![1.0, 2.0, 3.0], vec![4.0, 1.0, 0.0]];
+        let x2 = vec![
+            vec![0.0, 0.0, 0.0],
+            vec![1.0, 1.0, 1.0],
+            vec![2.0, 2.0, 2.0],
+        ];
+        let d = euclidean(x1.clone(), Some(x2.clone()), false).unwrap();
+        for (i, a) in x1.iter().enumerate() {
+            for (j, b) in x2.iter().enumerate() {
+                let expected: f64 = a
+                    .iter()
+                    .zip(b.iter())
+                    .map(|(x, y)| (x - y).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                assert!(
+                    (d[i][j] - expected).abs() < 1e-9,
+                    "mismatch at ({i}, {j}): expected {expected}, got {}",
+                    d[i][j]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lmfit_recovers_period_and_fits_well() {
+        let n = 50;
+        let true_period = 12.0;
+        let y: Vec<f64> = (0..n)
+            .map(|t| {
+                let t = t as f64;
+                5.0 + 3.0 * (2.0 * std::f64::consts::PI * t / true_period).sin() + 0.05 * t
+            })
+            .collect();
+
+        let theta = lmfit(&y);
+        let fitted_period = theta[2].abs();
+        assert!(
+            (fitted_period - true_period).abs() < 2.0,
+            "expected period near {true_period}, got {fitted_period}"
+        );
+
+        let residual: f64 = (0..n)
+            .map(|t| (lmfit_model(&theta, t as f64) - y[t]).powi(2))
+            .sum();
+        assert!(residual < 1.0, "expected a low-residual fit, got {residual}");
+    }
+
+    #[test]
+    fn kmedoids_separates_two_obvious_clusters() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![0.1, 0.1],
+            vec![0.2, -0.1],
+            vec![10.0, 10.0],
+            vec![10.1, 9.9],
+            vec![9.9, 10.1],
+        ];
+        let distance_matrix = euclidean(points, None, false).unwrap();
+
+        let result = kmedoids(&distance_matrix, 2, 42);
+        assert_eq!(result.medoids.len(), 2);
+        assert_eq!(result.labels.len(), 6);
+
+        let first_group = result.labels[0];
+        for &label in &result.labels[1..3] {
+            assert_eq!(label, first_group, "expected points 0..3 in the same cluster");
+        }
+        let second_group = result.labels[3];
+        assert_ne!(first_group, second_group, "expected the two groups in different clusters");
+        for &label in &result.labels[4..6] {
+            assert_eq!(label, second_group, "expected points 3..6 in the same cluster");
+        }
+    }
+
+    #[test]
+    fn kmedoids_empty_distance_matrix_does_not_panic() {
+        let result = kmedoids(&[], 3, 0);
+        assert!(result.labels.is_empty());
+        assert!(result.medoids.is_empty());
+    }
+
+    #[test]
+    fn cross_correlation_matches_direct_reference() {
+        // `cross_correlation` zero-pads to a power of two and returns the
+        // circular cross-correlation, so its entries are a reordering (plus
+        // zero-valued padding lags) of `cross_correlation_direct`'s linear lags
+        // rather than a value-for-value match — but both must agree on the
+        // best-aligning lag's correlation, which is the only thing callers
+        // like `sbd` actually read.
+        let cases: Vec<(Vec<f64>, Vec<f64>)> = vec![
+            (vec![1.0, 2.0, 3.0, 4.0], vec![4.0, 3.0, 2.0, 1.0]),
+            (vec![1.0, -2.0, 3.0, -4.0, 5.0], vec![0.5, 1.5, -2.5]),
+            (vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![1.0, 0.0, -1.0, 0.5]),
+        ];
+
+        for (a, b) in cases {
+            let fft_max = cross_correlation(&a, &b)
+                .into_iter()
+                .fold(f64::MIN, f64::max);
+            let direct_max = cross_correlation_direct(&a, &b)
+                .into_iter()
+                .fold(f64::MIN, f64::max);
+            assert!(
+                (fft_max - direct_max).abs() < 1e-6,
+                "a.len()={}, b.len()={}: fft max {fft_max} vs direct max {direct_max}",
+                a.len(),
+                b.len()
+            );
+        }
+    }
+
+    #[test]
+    fn sbd_auto_method_matches_explicit_method_on_both_sides_of_the_threshold() {
+        // Short series: "auto" should behave like "direct" (below SBD_DIRECT_THRESHOLD).
+        let short_x1 = vec![vec![1.0, 2.0, 3.0, 2.0, 1.0, 0.0]];
+        let short_x2 = vec![vec![0.0, 1.0, 2.0, 3.0, 2.0, 1.0]];
+        let auto_short = sbd(short_x1.clone(), Some(short_x2.clone()), false, "auto").unwrap();
+        let direct_short = sbd(short_x1, Some(short_x2), false, "direct").unwrap();
+        assert!(
+            (auto_short[0][0] - direct_short[0][0]).abs() < 1e-9,
+            "auto {} vs direct {} for a short series pair",
+            auto_short[0][0],
+            direct_short[0][0]
+        );
+
+        // Long series: "auto" should behave like "fft" (at/above SBD_DIRECT_THRESHOLD).
+        let n = SBD_DIRECT_THRESHOLD + 10;
+        let long_x1 = vec![
+            (0..n)
+                .map(|t| (t as f64 * 0.2).sin())
+                .collect::<Vec<f64>>(),
+        ];
+        let long_x2 = vec![
+            (0..n)
+                .map(|t| (t as f64 * 0.2 + 0.3).sin())
+                .collect::<Vec<f64>>(),
+        ];
+        let auto_long = sbd(long_x1.clone(), Some(long_x2.clone()), false, "auto").unwrap();
+        let fft_long = sbd(long_x1, Some(long_x2), false, "fft").unwrap();
+        assert!(
+            (auto_long[0][0] - fft_long[0][0]).abs() < 1e-6,
+            "auto {} vs fft {} for a long series pair",
+            auto_long[0][0],
+            fft_long[0][0]
+        );
+    }
+
+    #[test]
+    fn knn_euclidean_returns_hand_computed_nearest_neighbors() {
+        // Query at the origin against four points at known Euclidean
+        // distances 1, 2, 3, 4 along the x-axis (in `x2` order 3, 1, 4, 2).
+        let x1 = vec![vec![0.0, 0.0]];
+        let x2 = vec![
+            vec![3.0, 0.0],
+            vec![1.0, 0.0],
+            vec![4.0, 0.0],
+            vec![2.0, 0.0],
+        ];
+
+        let result = knn_euclidean(x1, x2, 3, false).unwrap();
+        assert_eq!(result.indices.len(), 1);
+        assert_eq!(result.indices[0], vec![1, 3, 0], "expected the 3 closest points, nearest first");
+        let expected_distances = [1.0, 2.0, 3.0];
+        for (got, want) in result.distances[0].iter().zip(expected_distances) {
+            assert!((got - want).abs() < 1e-9, "got {got}, want {want}");
+        }
+    }
+
+    #[test]
+    fn knn_euclidean_caps_k_at_the_number_of_candidates() {
+        let x1 = vec![vec![0.0]];
+        let x2 = vec![vec![1.0], vec![2.0]];
+        let result = knn_euclidean(x1, x2, 10, false).unwrap();
+        assert_eq!(result.indices[0].len(), 2);
+        assert_eq!(result.indices[0], vec![0, 1]);
+    }
+}
@@ -0,0 +1,456 @@
+//! Python bindings (PyO3) exposing the `core` distance functions.
+//!
+//! Each distance is registered twice in its `tsdistances` submodule (see
+//! `lib.rs`, which also re-exports it at the top level): once as a
+//! plain blocking `#[pyfunction]` and once as an `_async` variant that
+//! hands back an `asyncio` coroutine. The async variants offload the
+//! underlying DP to a background thread via [`spawn_blocking`], so the
+//! GIL is released for the duration of the computation and a caller can
+//! `await asyncio.gather(...)` over many pairs without stalling the
+//! event loop.
+
+use pyo3::exceptions::{PyKeyboardInterrupt, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::core::{self, DistanceError};
+
+fn to_pyerr(err: DistanceError) -> PyErr {
+    match err {
+        DistanceError::Interrupted => PyKeyboardInterrupt::new_err("tsdistances computation interrupted"),
+        other => PyValueError::new_err(other.to_string()),
+    }
+}
+
+/// Runs `f` on the tokio blocking thread pool and returns an awaitable
+/// that resolves to its result, releasing the GIL while `f` runs.
+fn spawn_async<'py>(
+    py: Python<'py>,
+    f: impl FnOnce() -> core::Result<Vec<Vec<f64>>> + Send + 'static,
+) -> PyResult<Bound<'py, PyAny>> {
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let result = tokio::task::spawn_blocking(f)
+            .await
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?
+            .map_err(to_pyerr)?;
+        Python::with_gil(|py| Ok(result.into_py(py)))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn euclidean(x1: Vec<Vec<f64>>, x2: Option<Vec<Vec<f64>>>, par: bool) -> PyResult<Vec<Vec<f64>>> {
+    core::euclidean(x1, x2, par).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn euclidean_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    spawn_async(py, move || core::euclidean(x1, x2, par))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn catch_euclidean(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::catch_euclidean(x1, x2, par).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn lmfit_euclidean(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::lmfit_euclidean(x1, x2, par).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn lmfit_euclidean_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    spawn_async(py, move || core::lmfit_euclidean(x1, x2, par))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true, device="cpu"))]
+pub fn cosine(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::cosine(x1, x2, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true, device="cpu"))]
+pub fn cosine_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || core::cosine(x1, x2, par, &device))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn angular(x1: Vec<Vec<f64>>, x2: Option<Vec<Vec<f64>>>, par: bool) -> PyResult<Vec<Vec<f64>>> {
+    core::angular(x1, x2, par).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn angular_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    spawn_async(py, move || core::angular(x1, x2, par))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true))]
+pub fn catch_euclidean_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    spawn_async(py, move || core::catch_euclidean(x1, x2, par))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, gap_penalty=0.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn erp(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    gap_penalty: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::erp(x1, x2, sakoe_chiba_band, gap_penalty, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, gap_penalty=0.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn erp_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    gap_penalty: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || {
+        core::erp(x1, x2, sakoe_chiba_band, gap_penalty, par, &device)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, epsilon=1.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn lcss(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    epsilon: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::lcss(x1, x2, sakoe_chiba_band, epsilon, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, epsilon=1.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn lcss_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    epsilon: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || {
+        core::lcss(x1, x2, sakoe_chiba_band, epsilon, par, &device)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, par=true, device="cpu"))]
+pub fn dtw(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::dtw(x1, x2, sakoe_chiba_band, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, par=true, device="cpu"))]
+pub fn dtw_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || core::dtw(x1, x2, sakoe_chiba_band, par, &device))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, par=true, device="cpu"))]
+pub fn ddtw(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::ddtw(x1, x2, sakoe_chiba_band, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, par=true, device="cpu"))]
+pub fn ddtw_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || core::ddtw(x1, x2, sakoe_chiba_band, par, &device))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, g=0.05, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn wdtw(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    g: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::wdtw(x1, x2, sakoe_chiba_band, g, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, g=0.05, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn wdtw_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    g: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || {
+        core::wdtw(x1, x2, sakoe_chiba_band, g, par, &device)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, g=0.05, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn wddtw(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    g: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::wddtw(x1, x2, sakoe_chiba_band, g, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, g=0.05, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn wddtw_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    g: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || {
+        core::wddtw(x1, x2, sakoe_chiba_band, g, par, &device)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, warp_penalty=1.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn adtw(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    warp_penalty: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::adtw(x1, x2, sakoe_chiba_band, warp_penalty, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, warp_penalty=1.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn adtw_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    warp_penalty: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || {
+        core::adtw(x1, x2, sakoe_chiba_band, warp_penalty, par, &device)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, par=true, device="cpu"))]
+pub fn msm(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::msm(x1, x2, sakoe_chiba_band, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, par=true, device="cpu"))]
+pub fn msm_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || core::msm(x1, x2, sakoe_chiba_band, par, &device))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, stiffness=0.001, penalty=1.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn twe(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    stiffness: f64,
+    penalty: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::twe(x1, x2, sakoe_chiba_band, stiffness, penalty, par, device).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, sakoe_chiba_band=1.0, stiffness=0.001, penalty=1.0, par=true, device="cpu"))]
+#[allow(clippy::too_many_arguments)]
+pub fn twe_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    sakoe_chiba_band: f64,
+    stiffness: f64,
+    penalty: f64,
+    par: bool,
+    device: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let device = device.to_string();
+    spawn_async(py, move || {
+        core::twe(x1, x2, sakoe_chiba_band, stiffness, penalty, par, &device)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true, method="auto"))]
+pub fn sb(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+    method: &str,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::sbd(x1, x2, par, method).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, par=true, method="auto"))]
+pub fn sb_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    par: bool,
+    method: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    let method = method.to_string();
+    spawn_async(py, move || core::sbd(x1, x2, par, &method))
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, window=10, par=true))]
+pub fn mp(
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    window: i32,
+    par: bool,
+) -> PyResult<Vec<Vec<f64>>> {
+    core::mp(x1, x2, window, par).map_err(to_pyerr)
+}
+
+#[pyfunction]
+#[pyo3(signature = (x1, x2=None, window=10, par=true))]
+pub fn mp_async<'py>(
+    py: Python<'py>,
+    x1: Vec<Vec<f64>>,
+    x2: Option<Vec<Vec<f64>>>,
+    window: i32,
+    par: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    spawn_async(py, move || core::mp(x1, x2, window, par))
+}
@@ -15,28 +15,130 @@ pub mod matlab_ffi;
 
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+#[cfg(feature = "python")]
+use pyo3::wrap_pymodule;
 
+// The `_async` bindings hand computation off to this runtime so the
+// GIL can be released for the duration of each DP; built once and
+// shared across every coroutine spawned by `py_module`.
 #[cfg(feature = "python")]
-#[pymodule]
-#[pyo3(name = "tsdistances")]
-fn py_module(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
-    let _ = ctrlc::set_handler(move || {
-        println!("\nraise KeyboardInterrupt (Ctrl+C pressed)");
-        std::process::exit(1);
-    });
+static TOKIO_RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
 
-    m.add_function(wrap_pyfunction!(distances::euclidean, m)?)?;
-    m.add_function(wrap_pyfunction!(distances::catch_euclidean, m)?)?;
-    m.add_function(wrap_pyfunction!(distances::erp, m)?)?;
-    m.add_function(wrap_pyfunction!(distances::lcss, m)?)?;
+#[cfg(feature = "python")]
+fn tokio_runtime() -> &'static tokio::runtime::Runtime {
+    TOKIO_RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("failed to start tsdistances async runtime")
+    })
+}
+
+/// `tsdistances.elastic` — distances defined over a DP wavefront
+/// (`diagonal::diagonal_distance`).
+#[cfg(feature = "python")]
+#[pymodule]
+#[pyo3(name = "elastic")]
+fn elastic_module(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(distances::dtw, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::dtw_async, m)?)?;
     m.add_function(wrap_pyfunction!(distances::ddtw, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::ddtw_async, m)?)?;
     m.add_function(wrap_pyfunction!(distances::wdtw, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::wdtw_async, m)?)?;
     m.add_function(wrap_pyfunction!(distances::wddtw, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::wddtw_async, m)?)?;
     m.add_function(wrap_pyfunction!(distances::adtw, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::adtw_async, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::erp, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::erp_async, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::lcss, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::lcss_async, m)?)?;
     m.add_function(wrap_pyfunction!(distances::msm, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::msm_async, m)?)?;
     m.add_function(wrap_pyfunction!(distances::twe, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::twe_async, m)?)?;
+    Ok(())
+}
+
+/// `tsdistances.lockstep` — fixed-alignment (non-elastic) distances.
+#[cfg(feature = "python")]
+#[pymodule]
+#[pyo3(name = "lockstep")]
+fn lockstep_module(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(distances::euclidean, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::euclidean_async, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::catch_euclidean, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::catch_euclidean_async, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::lmfit_euclidean, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::lmfit_euclidean_async, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::cosine, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::cosine_async, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::angular, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::angular_async, m)?)?;
+    Ok(())
+}
+
+/// `tsdistances.matrix_profile` — shape-based and matrix-profile measures.
+#[cfg(feature = "python")]
+#[pymodule]
+#[pyo3(name = "matrix_profile")]
+fn matrix_profile_module(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(distances::sb, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::sb_async, m)?)?;
     m.add_function(wrap_pyfunction!(distances::mp, m)?)?;
+    m.add_function(wrap_pyfunction!(distances::mp_async, m)?)?;
+    Ok(())
+}
+
+/// Registers `submodule` as a proper attribute of `parent` and inserts it
+/// into `sys.modules` under its dotted name, so `import parent.submodule`
+/// works. The `__module__` of the functions inside `submodule` is not
+/// changed by this.
+#[cfg(feature = "python")]
+fn register_submodule(
+    py: Python,
+    parent: &Bound<PyModule>,
+    submodule: &Bound<PyModule>,
+) -> PyResult<()> {
+    let dotted_name = format!("{}.{}", parent.name()?, submodule.name()?);
+    py.import("sys")?
+        .getattr("modules")?
+        .set_item(&dotted_name, submodule)?;
+    parent.add_submodule(submodule)
+}
+
+#[cfg(feature = "python")]
+#[pymodule]
+#[pyo3(name = "tsdistances")]
+fn py_module(py: Python, m: &Bound<PyModule>) -> PyResult<()> {
+    // Cooperative cancellation: flag the running computation and let it
+    // unwind on its own rather than tearing down the whole interpreter.
+    let _ = ctrlc::set_handler(move || {
+        utils::request_interrupt();
+    });
+
+    pyo3_asyncio::tokio::init_with_runtime(tokio_runtime())
+        .expect("failed to install tsdistances async runtime");
+
+    let elastic = wrap_pymodule!(elastic_module)(py);
+    let lockstep = wrap_pymodule!(lockstep_module)(py);
+    let matrix_profile = wrap_pymodule!(matrix_profile_module)(py);
+
+    register_submodule(py, m, elastic.bind(py))?;
+    register_submodule(py, m, lockstep.bind(py))?;
+    register_submodule(py, m, matrix_profile.bind(py))?;
+
+    // Re-export the full flat set at the top level for backward compatibility.
+    for submodule in [elastic.bind(py), lockstep.bind(py), matrix_profile.bind(py)] {
+        for (name, value) in submodule.getattr("__dict__")?.downcast::<PyDict>()?.iter() {
+            if value.is_callable() {
+                m.add(name.extract::<&str>()?, value)?;
+            }
+        }
+    }
+
     Ok(())
 }
@@ -3,6 +3,8 @@
 //! This module provides C-compatible functions that can be called from MATLAB
 //! through MEX files.
 
+use std::ffi::CStr;
+use std::os::raw::c_char;
 use std::slice;
 
 use crate::core;
@@ -161,6 +163,76 @@ pub unsafe extern "C" fn tsd_catch_euclidean(
     }
 }
 
+/// Compute LM-fit-Euclidean distance matrix (Euclidean distance between
+/// Levenberg-Marquardt-fitted periodic + linear trend model parameters)
+///
+/// # Safety
+/// All pointers must be valid. x2_data can be null for pairwise distance within x1.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsd_lmfit_euclidean(
+    x1_data: *const f64,
+    x1_rows: usize,
+    x1_cols: usize,
+    x2_data: *const f64,
+    x2_rows: usize,
+    x2_cols: usize,
+    parallel: bool,
+) -> DistanceResult {
+    let (x1, x2) =
+        unsafe { c_arrays_to_vecs(x1_data, x1_rows, x1_cols, x2_data, x2_rows, x2_cols) };
+
+    match core::lmfit_euclidean(x1, x2, parallel) {
+        Ok(result) => DistanceResult::success(result),
+        Err(_) => DistanceResult::error(1),
+    }
+}
+
+/// Compute cosine distance matrix
+///
+/// # Safety
+/// All pointers must be valid. x2_data can be null for pairwise distance within x1.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsd_cosine(
+    x1_data: *const f64,
+    x1_rows: usize,
+    x1_cols: usize,
+    x2_data: *const f64,
+    x2_rows: usize,
+    x2_cols: usize,
+    parallel: bool,
+) -> DistanceResult {
+    let (x1, x2) =
+        unsafe { c_arrays_to_vecs(x1_data, x1_rows, x1_cols, x2_data, x2_rows, x2_cols) };
+
+    match core::cosine(x1, x2, parallel, "cpu") {
+        Ok(result) => DistanceResult::success(result),
+        Err(_) => DistanceResult::error(1),
+    }
+}
+
+/// Compute angular distance matrix
+///
+/// # Safety
+/// All pointers must be valid. x2_data can be null for pairwise distance within x1.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsd_angular(
+    x1_data: *const f64,
+    x1_rows: usize,
+    x1_cols: usize,
+    x2_data: *const f64,
+    x2_rows: usize,
+    x2_cols: usize,
+    parallel: bool,
+) -> DistanceResult {
+    let (x1, x2) =
+        unsafe { c_arrays_to_vecs(x1_data, x1_rows, x1_cols, x2_data, x2_rows, x2_cols) };
+
+    match core::angular(x1, x2, parallel) {
+        Ok(result) => DistanceResult::success(result),
+        Err(_) => DistanceResult::error(1),
+    }
+}
+
 /// Compute ERP (Edit Distance with Real Penalty) distance matrix
 ///
 /// # Safety
@@ -400,12 +472,149 @@ pub unsafe extern "C" fn tsd_sbd(
     let (x1, x2) =
         unsafe { c_arrays_to_vecs(x1_data, x1_rows, x1_cols, x2_data, x2_rows, x2_cols) };
 
-    match core::sbd(x1, x2, parallel) {
+    match core::sbd(x1, x2, parallel, "auto") {
         Ok(result) => DistanceResult::success(result),
         Err(_) => DistanceResult::error(1),
     }
 }
 
+/// Result structure for returning k-nearest-neighbor queries to MATLAB:
+/// two row-major m*k arrays (neighbor distance and its column index into
+/// x2), ordered by ascending distance within each query row.
+#[repr(C)]
+pub struct KnnResult {
+    /// Pointer to the distances (row-major, m rows by k columns)
+    pub distances: *mut f64,
+    /// Pointer to the column indices into x2 (row-major, m rows by k columns)
+    pub indices: *mut usize,
+    /// Number of query rows (rows of x1)
+    pub rows: usize,
+    /// Number of neighbors returned per query row
+    pub k: usize,
+    /// Error code: 0 = success, non-zero = error
+    pub error_code: i32,
+}
+
+impl KnnResult {
+    fn success(result: core::KnnResult) -> Self {
+        let rows = result.indices.len();
+        let k = if rows > 0 { result.indices[0].len() } else { 0 };
+
+        // Flatten row-major: each query row's k neighbors are contiguous.
+        let mut flat_distances: Vec<f64> = Vec::with_capacity(rows * k);
+        let mut flat_indices: Vec<usize> = Vec::with_capacity(rows * k);
+        for row in 0..rows {
+            for col in 0..k {
+                flat_distances.push(result.distances[row][col]);
+                flat_indices.push(result.indices[row][col]);
+            }
+        }
+
+        let mut distances = flat_distances.into_boxed_slice();
+        let distances_ptr = distances.as_mut_ptr();
+        std::mem::forget(distances);
+
+        let mut indices = flat_indices.into_boxed_slice();
+        let indices_ptr = indices.as_mut_ptr();
+        std::mem::forget(indices);
+
+        KnnResult {
+            distances: distances_ptr,
+            indices: indices_ptr,
+            rows,
+            k,
+            error_code: 0,
+        }
+    }
+
+    fn error(code: i32) -> Self {
+        KnnResult {
+            distances: std::ptr::null_mut(),
+            indices: std::ptr::null_mut(),
+            rows: 0,
+            k: 0,
+            error_code: code,
+        }
+    }
+}
+
+/// Free memory allocated for a KnnResult
+///
+/// # Safety
+/// The caller must ensure that the pointer was allocated by this library
+/// and has not been freed before.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsd_free_knn_result(result: *mut KnnResult) {
+    if !result.is_null() {
+        unsafe {
+            let res = &*result;
+            let size = res.rows * res.k;
+            if !res.distances.is_null() && size > 0 {
+                let _ = Vec::from_raw_parts(res.distances, size, size);
+            }
+            if !res.indices.is_null() && size > 0 {
+                let _ = Vec::from_raw_parts(res.indices, size, size);
+            }
+        }
+    }
+}
+
+/// Compute the k nearest neighbors in x2 for each row of x1 under Euclidean
+/// distance, without materializing the full m x n distance matrix.
+///
+/// # Safety
+/// All pointers must be valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsd_knn_euclidean(
+    x1_data: *const f64,
+    x1_rows: usize,
+    x1_cols: usize,
+    x2_data: *const f64,
+    x2_rows: usize,
+    x2_cols: usize,
+    k: usize,
+    parallel: bool,
+) -> KnnResult {
+    let (x1, x2) =
+        unsafe { c_arrays_to_vecs(x1_data, x1_rows, x1_cols, x2_data, x2_rows, x2_cols) };
+    let Some(x2) = x2 else {
+        return KnnResult::error(1);
+    };
+
+    match core::knn_euclidean(x1, x2, k, parallel) {
+        Ok(result) => KnnResult::success(result),
+        Err(_) => KnnResult::error(1),
+    }
+}
+
+/// Compute the k nearest neighbors in x2 for each row of x1 under DTW.
+///
+/// # Safety
+/// All pointers must be valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsd_knn_dtw(
+    x1_data: *const f64,
+    x1_rows: usize,
+    x1_cols: usize,
+    x2_data: *const f64,
+    x2_rows: usize,
+    x2_cols: usize,
+    sakoe_chiba_band: f64,
+    k: usize,
+    parallel: bool,
+) -> KnnResult {
+    let (x1, x2) =
+        unsafe { c_arrays_to_vecs(x1_data, x1_rows, x1_cols, x2_data, x2_rows, x2_cols) };
+    let Some(x2) = x2 else {
+        return KnnResult::error(1);
+    };
+
+    match core::knn_dtw(x1, x2, sakoe_chiba_band, k, parallel) {
+        Ok(result) => KnnResult::success(result),
+        Err(_) => KnnResult::error(1),
+    }
+}
+
 /// Compute MP (Matrix Profile) distance matrix
 ///
 /// # Safety
@@ -429,3 +638,152 @@ pub unsafe extern "C" fn tsd_mp(
         Err(_) => DistanceResult::error(1),
     }
 }
+
+/// Result structure for returning a k-medoids clustering to MATLAB: a
+/// per-series cluster label array plus the row indices of the chosen
+/// medoids, with the same error-code/free conventions as `DistanceResult`.
+#[repr(C)]
+pub struct KMedoidsResult {
+    /// Pointer to the per-series cluster labels (length `rows`, values in `0..k`)
+    pub labels: *mut usize,
+    /// Pointer to the row indices of the final medoids (length `k`)
+    pub medoids: *mut usize,
+    /// Number of input series
+    pub rows: usize,
+    /// Number of clusters
+    pub k: usize,
+    /// Error code: 0 = success, non-zero = error
+    pub error_code: i32,
+}
+
+impl KMedoidsResult {
+    fn success(result: core::KMedoidsResult) -> Self {
+        let rows = result.labels.len();
+        let k = result.medoids.len();
+
+        let mut labels = result.labels.into_boxed_slice();
+        let labels_ptr = labels.as_mut_ptr();
+        std::mem::forget(labels);
+
+        let mut medoids = result.medoids.into_boxed_slice();
+        let medoids_ptr = medoids.as_mut_ptr();
+        std::mem::forget(medoids);
+
+        KMedoidsResult {
+            labels: labels_ptr,
+            medoids: medoids_ptr,
+            rows,
+            k,
+            error_code: 0,
+        }
+    }
+
+    fn error(code: i32) -> Self {
+        KMedoidsResult {
+            labels: std::ptr::null_mut(),
+            medoids: std::ptr::null_mut(),
+            rows: 0,
+            k: 0,
+            error_code: code,
+        }
+    }
+}
+
+/// Free memory allocated for a KMedoidsResult
+///
+/// # Safety
+/// The caller must ensure that the pointer was allocated by this library
+/// and has not been freed before.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsd_free_kmedoids_result(result: *mut KMedoidsResult) {
+    if !result.is_null() {
+        unsafe {
+            let res = &*result;
+            if !res.labels.is_null() && res.rows > 0 {
+                let _ = Vec::from_raw_parts(res.labels, res.rows, res.rows);
+            }
+            if !res.medoids.is_null() && res.k > 0 {
+                let _ = Vec::from_raw_parts(res.medoids, res.k, res.k);
+            }
+        }
+    }
+}
+
+/// Computes the distance matrix for `x1` under the named measure, reading
+/// its parameters positionally out of `params` (unset slots default to the
+/// same values the Python/MATLAB bindings use elsewhere in this crate).
+/// Only the measures this crate already exposes through FFI are wired up
+/// here; adding another is a matter of following the same pattern.
+fn compute_measure(
+    measure: &str,
+    x1: Vec<Vec<f64>>,
+    params: &[f64],
+    par: bool,
+) -> core::Result<Vec<Vec<f64>>> {
+    let param = |i: usize, default: f64| params.get(i).copied().unwrap_or(default);
+
+    match measure {
+        "euclidean" => core::euclidean(x1, None, par),
+        "catch_euclidean" => core::catch_euclidean(x1, None, par),
+        "lmfit_euclidean" => core::lmfit_euclidean(x1, None, par),
+        "cosine" => core::cosine(x1, None, par, "cpu"),
+        "angular" => core::angular(x1, None, par),
+        "sbd" => core::sbd(x1, None, par, "auto"),
+        "dtw" => core::dtw(x1, None, param(0, 1.0), par, "cpu"),
+        "ddtw" => core::ddtw(x1, None, param(0, 1.0), par, "cpu"),
+        "wdtw" => core::wdtw(x1, None, param(0, 1.0), param(1, 0.05), par, "cpu"),
+        "wddtw" => core::wddtw(x1, None, param(0, 1.0), param(1, 0.05), par, "cpu"),
+        "adtw" => core::adtw(x1, None, param(0, 1.0), param(1, 1.0), par, "cpu"),
+        "erp" => core::erp(x1, None, param(0, 1.0), param(1, 0.0), par, "cpu"),
+        "lcss" => core::lcss(x1, None, param(0, 1.0), param(1, 1.0), par, "cpu"),
+        "msm" => core::msm(x1, None, param(0, 1.0), par, "cpu"),
+        "twe" => core::twe(x1, None, 1.0, param(0, 0.001), param(1, 1.0), par, "cpu"),
+        other => Err(core::DistanceError::InvalidParameter(format!(
+            "Unknown measure '{other}'"
+        ))),
+    }
+}
+
+/// Clusters `x1` into `k` groups via Partitioning Around Medoids (PAM)
+/// under the named distance `measure`, going straight from raw series to
+/// cluster labels without the caller having to compute and manage a
+/// distance matrix itself. `measure` selects one of the distances this
+/// crate already exposes (e.g. `"euclidean"`, `"dtw"`, `"msm"`, `"twe"`);
+/// `params` supplies that measure's extra parameters positionally (e.g.
+/// `[sakoe_chiba_band]` for DTW), and may be null/empty to use defaults.
+///
+/// # Safety
+/// All pointers must be valid. `measure` must be a NUL-terminated C string.
+/// `params` may be null only if `params_len` is 0.
+#[unsafe(no_mangle)]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn tsd_kmedoids(
+    x1_data: *const f64,
+    x1_rows: usize,
+    x1_cols: usize,
+    measure: *const c_char,
+    params: *const f64,
+    params_len: usize,
+    k: usize,
+    seed: u64,
+    parallel: bool,
+) -> KMedoidsResult {
+    let (x1, _) = unsafe { c_arrays_to_vecs(x1_data, x1_rows, x1_cols, std::ptr::null(), 0, 0) };
+
+    let measure = match unsafe { CStr::from_ptr(measure) }.to_str() {
+        Ok(s) => s,
+        Err(_) => return KMedoidsResult::error(1),
+    };
+    let params = if params.is_null() {
+        &[][..]
+    } else {
+        unsafe { slice::from_raw_parts(params, params_len) }
+    };
+
+    let distance_matrix = match compute_measure(measure, x1, params, parallel) {
+        Ok(matrix) => matrix,
+        Err(_) => return KMedoidsResult::error(1),
+    };
+
+    KMedoidsResult::success(core::kmedoids(&distance_matrix, k, seed))
+}
@@ -1,5 +1,62 @@
-use rustfft::{Fft, FftPlanner, num_complex::Complex};
-use std::{cell::RefCell, collections::HashMap, sync::Arc};
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use rustfft::num_complex::Complex;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+/// Set by the Ctrl+C handler installed in `py_module`; polled by the DP
+/// kernels so a long-running computation can unwind cooperatively instead
+/// of the process being torn down with `process::exit`.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Count of top-level calls (`compute_distance`, `euclidean`, `knn`, ...)
+/// currently in flight. The `_async` bindings hand computations off to
+/// tokio's blocking pool, so two calls can genuinely overlap (e.g. via
+/// `asyncio.gather`); this lets [`begin_call`] tell "I'm the only call
+/// running, it's safe to start clean" apart from "another call is already
+/// running and may be sitting on a pending interrupt meant for it".
+static ACTIVE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Requests cancellation of every in-flight distance computation.
+pub fn request_interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Polled by the loops in `core`: once per row or pair of series, once per
+/// row block of `euclidean`'s Gram matrix, and once per outer DP row of
+/// `twe_soft_with_grad`. The `diagonal`/`matrix` wavefronts do not poll it.
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// RAII guard returned by [`begin_call`]; marks its call as finished when
+/// dropped, regardless of how the call returns (success, error, or panic).
+pub struct CallGuard(());
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        ACTIVE_CALLS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Marks the start of a top-level distance computation. Only clears a
+/// previously requested interrupt when this is the sole call in flight —
+/// if another call is already running, clearing here would silently
+/// swallow an interrupt meant for that other call (two concurrent
+/// `_async` calls can otherwise race on a shared clear of the flag, see
+/// the chunk0-3 follow-up review). Hold the returned guard for the
+/// duration of the call.
+pub fn begin_call() -> CallGuard {
+    if ACTIVE_CALLS.fetch_add(1, Ordering::SeqCst) == 0 {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+    }
+    CallGuard(())
+}
 
 pub fn min<T: PartialOrd>(x: T, y: T) -> T {
     if x < y { x } else { y }
@@ -48,70 +105,110 @@ pub fn cross_correlation(a: &[f64], b: &[f64]) -> Vec<f64> {
 
     FFT_CACHE.with(|cache| {
         let mut cache = cache.borrow_mut();
-        let (fft, ifft) = cache.get_plans(fft_len);
         cache.ensure_len(fft_len);
+        let (r2c, c2r) = cache.get_plans(fft_len);
 
-        cache.a_fft.fill(Complex::new(0.0, 0.0));
-        cache.b_fft.fill(Complex::new(0.0, 0.0));
-        for (i, val) in a.iter().enumerate() {
-            cache.a_fft[i].re = *val;
-        }
-        for (i, val) in b.iter().enumerate() {
-            cache.b_fft[i].re = *val;
-        }
+        cache.a_time.fill(0.0);
+        cache.b_time.fill(0.0);
+        cache.a_time[..a.len()].copy_from_slice(a);
+        cache.b_time[..b.len()].copy_from_slice(b);
 
-        fft.process(&mut cache.a_fft);
-        fft.process(&mut cache.b_fft);
+        r2c.process(&mut cache.a_time, &mut cache.a_freq)
+            .expect("real FFT of a");
+        r2c.process(&mut cache.b_time, &mut cache.b_freq)
+            .expect("real FFT of b");
 
-        for i in 0..fft_len {
-            cache.c_fft[i] = cache.a_fft[i].conj() * cache.b_fft[i];
+        for i in 0..cache.c_freq.len() {
+            cache.c_freq[i] = cache.a_freq[i].conj() * cache.b_freq[i];
         }
 
-        ifft.process(&mut cache.c_fft);
-        for i in 0..fft_len {
-            cache.c[i] = cache.c_fft[i].re / fft_len as f64;
-        }
-        cache.c.clone()
+        c2r.process(&mut cache.c_freq, &mut cache.c_time)
+            .expect("inverse real FFT of cross-spectrum");
+        // realfft's inverse transform is unnormalized, matching rustfft's convention.
+        cache
+            .c_time
+            .iter()
+            .map(|val| val / fft_len as f64)
+            .collect()
     })
 }
 
+/// Brute-force O(n·m) cross-correlation, kept as a fallback for very short
+/// series where the FFT's planning/allocation overhead dominates its
+/// asymptotic advantage. Returns the same multiset of lag correlations as
+/// [`cross_correlation`] (the FFT path only adds zero-padding beyond the
+/// `n+m-1` valid lags), so `.max()` over either agrees whenever some lag's
+/// correlation is non-negative, which always holds for `sbd`'s z-scored
+/// inputs (their lag correlations sum to zero).
+pub fn cross_correlation_direct(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let min_lag = -(m - 1);
+    let max_lag = n - 1;
+
+    (min_lag..=max_lag)
+        .map(|lag| {
+            (0..n)
+                .filter_map(|i| {
+                    let j = i - lag;
+                    (j >= 0 && j < m).then(|| a[i as usize] * b[j as usize])
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Caches real-to-complex/complex-to-real FFT plans and their scratch
+/// buffers per thread. Using the real-input specialization instead of a
+/// full complex FFT halves both the spectral buffer size and the transform
+/// work for `cross_correlation`'s real-valued series.
 struct FftCache {
-    planner: FftPlanner<f64>,
-    plans: HashMap<usize, (Arc<dyn Fft<f64>>, Arc<dyn Fft<f64>>)>,
-    a_fft: Vec<Complex<f64>>,
-    b_fft: Vec<Complex<f64>>,
-    c_fft: Vec<Complex<f64>>,
-    c: Vec<f64>,
+    planner: RealFftPlanner<f64>,
+    plans: HashMap<usize, (Arc<dyn RealToComplex<f64>>, Arc<dyn ComplexToReal<f64>>)>,
+    a_time: Vec<f64>,
+    b_time: Vec<f64>,
+    c_time: Vec<f64>,
+    a_freq: Vec<Complex<f64>>,
+    b_freq: Vec<Complex<f64>>,
+    c_freq: Vec<Complex<f64>>,
 }
 
 impl FftCache {
     fn new() -> Self {
         Self {
-            planner: FftPlanner::new(),
+            planner: RealFftPlanner::new(),
             plans: HashMap::new(),
-            a_fft: Vec::new(),
-            b_fft: Vec::new(),
-            c_fft: Vec::new(),
-            c: Vec::new(),
+            a_time: Vec::new(),
+            b_time: Vec::new(),
+            c_time: Vec::new(),
+            a_freq: Vec::new(),
+            b_freq: Vec::new(),
+            c_freq: Vec::new(),
         }
     }
 
-    fn get_plans(&mut self, len: usize) -> (Arc<dyn Fft<f64>>, Arc<dyn Fft<f64>>) {
-        if let Some((fft, ifft)) = self.plans.get(&len) {
-            return (fft.clone(), ifft.clone());
+    fn get_plans(
+        &mut self,
+        len: usize,
+    ) -> (Arc<dyn RealToComplex<f64>>, Arc<dyn ComplexToReal<f64>>) {
+        if let Some((r2c, c2r)) = self.plans.get(&len) {
+            return (r2c.clone(), c2r.clone());
         }
-        let fft = self.planner.plan_fft_forward(len);
-        let ifft = self.planner.plan_fft_inverse(len);
-        self.plans.insert(len, (fft.clone(), ifft.clone()));
-        (fft, ifft)
+        let r2c = self.planner.plan_fft_forward(len);
+        let c2r = self.planner.plan_fft_inverse(len);
+        self.plans.insert(len, (r2c.clone(), c2r.clone()));
+        (r2c, c2r)
     }
 
     fn ensure_len(&mut self, len: usize) {
-        if self.a_fft.len() != len {
-            self.a_fft.resize(len, Complex::new(0.0, 0.0));
-            self.b_fft.resize(len, Complex::new(0.0, 0.0));
-            self.c_fft.resize(len, Complex::new(0.0, 0.0));
-            self.c.resize(len, 0.0);
+        if self.a_time.len() != len {
+            let freq_len = len / 2 + 1;
+            self.a_time.resize(len, 0.0);
+            self.b_time.resize(len, 0.0);
+            self.c_time.resize(len, 0.0);
+            self.a_freq.resize(freq_len, Complex::new(0.0, 0.0));
+            self.b_freq.resize(freq_len, Complex::new(0.0, 0.0));
+            self.c_freq.resize(freq_len, Complex::new(0.0, 0.0));
         }
     }
 }